@@ -1,19 +1,95 @@
 //! Utility functions for Header implementations.
 
+use std::error;
 use std::str;
 use std::fmt;
-use time;
+
+/// An error encountered while parsing a raw header value with one of the
+/// utilities in this module.
+///
+/// Every utility here used to collapse all failures into `None`, which
+/// made it impossible to tell "this header had more than one raw line"
+/// apart from "this element didn't parse" while debugging a malformed
+/// upstream response. `from_one_raw_str` is kept as a thin adapter over
+/// `try_from_one_raw_str`. The `Option`-returning comma-delimited
+/// utilities still drop elements that fail to parse, whereas their
+/// `try_`-prefixed counterparts fail the whole header; the
+/// semicolon-delimited utilities have no `try_` counterparts.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum HeaderError {
+    /// The header was split into more or less than one raw line.
+    NotOneLine,
+    /// A raw line was not valid UTF-8.
+    Utf8,
+    /// An element of the value failed to parse via `FromStr`.
+    Parse,
+    /// The value was an HTTP-date that failed to parse.
+    Date(DateError),
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HeaderError::NotOneLine => f.write_str("header did not contain exactly one raw line"),
+            HeaderError::Utf8 => f.write_str("header line was not valid UTF-8"),
+            HeaderError::Parse => f.write_str("header element failed to parse"),
+            HeaderError::Date(ref e) => write!(f, "invalid HTTP-date: {}", e),
+        }
+    }
+}
+
+impl error::Error for HeaderError {
+    fn description(&self) -> &str {
+        match *self {
+            HeaderError::NotOneLine => "header did not contain exactly one raw line",
+            HeaderError::Utf8 => "header line was not valid UTF-8",
+            HeaderError::Parse => "header element failed to parse",
+            HeaderError::Date(_) => "invalid HTTP-date",
+        }
+    }
+}
+
+// Extracts the single raw line used by `try_from_one_raw_str` and
+// `try_http_date_from_one_raw_str` as a `&str`.
+fn one_raw_str(raw: &[Vec<u8>]) -> Result<&str, HeaderError> {
+    if raw.len() != 1 {
+        return Err(HeaderError::NotOneLine);
+    }
+    // we JUST checked that raw.len() == 1, so raw[0] WILL exist.
+    str::from_utf8(&raw[0][..]).map_err(|_| HeaderError::Utf8)
+}
+
+/// Reads a single raw string when parsing a header, reporting why
+/// parsing failed.
+pub fn try_from_one_raw_str<T: str::FromStr>(raw: &[Vec<u8>]) -> Result<T, HeaderError> {
+    let s = try!(one_raw_str(raw));
+    s.parse().map_err(|_| HeaderError::Parse)
+}
 
 /// Reads a single raw string when parsing a header
 pub fn from_one_raw_str<T: str::FromStr>(raw: &[Vec<u8>]) -> Option<T> {
+    try_from_one_raw_str(raw).ok()
+}
+
+/// Like `try_from_one_raw_str`, but for `HttpDate`s specifically,
+/// preserving the distinction `DateError` draws between an unrecognized
+/// date shape and a structurally valid date with an out-of-range field
+/// (e.g. an hour of 24).
+pub fn try_http_date_from_one_raw_str(raw: &[Vec<u8>]) -> Result<HttpDate, HeaderError> {
+    let s = try!(one_raw_str(raw));
+    str::FromStr::from_str(s).map_err(HeaderError::Date)
+}
+
+/// Reads a comma-delimited raw header into a Vec, reporting why parsing
+/// failed. Unlike `from_comma_delimited`, an element that fails to parse
+/// fails the whole header instead of silently being dropped.
+#[inline]
+pub fn try_from_comma_delimited<T: str::FromStr>(raw: &[Vec<u8>]) -> Result<Vec<T>, HeaderError> {
     if raw.len() != 1 {
-        return None;
+        return Err(HeaderError::NotOneLine);
     }
     // we JUST checked that raw.len() == 1, so raw[0] WILL exist.
-    match str::from_utf8(&raw[0][..]) {
-        Ok(s) => str::FromStr::from_str(s).ok(),
-        Err(_) => None
-    }
+    try_from_one_comma_delimited(&raw[0][..])
 }
 
 /// Reads a comma-delimited raw header into a Vec.
@@ -26,20 +102,145 @@ pub fn from_comma_delimited<T: str::FromStr>(raw: &[Vec<u8>]) -> Option<Vec<T>>
     from_one_comma_delimited(&raw[0][..])
 }
 
+/// Reads a comma-delimited raw string into a Vec, reporting why parsing
+/// failed. Unlike `from_one_comma_delimited`, an element that fails to
+/// parse fails the whole header instead of silently being dropped.
+pub fn try_from_one_comma_delimited<T: str::FromStr>(raw: &[u8]) -> Result<Vec<T>, HeaderError> {
+    let s = try!(str::from_utf8(raw).map_err(|_| HeaderError::Utf8));
+    let mut parts = Vec::new();
+    for part in split_delimited(s, b',').into_iter().map(|x| x.trim()) {
+        parts.push(try!(part.parse().map_err(|_| HeaderError::Parse)));
+    }
+    Ok(parts)
+}
+
 /// Reads a comma-delimited raw string into a Vec.
 pub fn from_one_comma_delimited<T: str::FromStr>(raw: &[u8]) -> Option<Vec<T>> {
+    from_one_raw_comma_delimited(raw).map(|parts| {
+        parts.into_iter().filter_map(|x| x.parse().ok()).collect()
+    })
+}
+
+/// Reads a comma-delimited raw string into a Vec of its raw element
+/// slices, without parsing each element via `FromStr`. Structured values
+/// (e.g. `WWW-Authenticate` challenge params) need the quotes a parsed
+/// `T` would otherwise strip, so they should split on this instead of
+/// `from_one_comma_delimited`.
+pub fn from_one_raw_comma_delimited(raw: &[u8]) -> Option<Vec<&str>> {
     match str::from_utf8(raw) {
-        Ok(s) => {
-            Some(s.as_slice()
-                 .split(',')
-                 .map(|x| x.trim())
-                 .filter_map(|x| x.parse().ok())
-                 .collect())
-        }
+        Ok(s) => Some(split_delimited(s, b',').into_iter().map(|x| x.trim()).collect()),
         Err(_) => None
     }
 }
 
+/// Reads a semicolon-delimited raw header into a Vec, mirroring
+/// `from_comma_delimited` for headers whose list separator is `;`
+/// (e.g. `Set-Cookie`'s attribute list).
+#[inline]
+pub fn from_semicolon_delimited<T: str::FromStr>(raw: &[Vec<u8>]) -> Option<Vec<T>> {
+    if raw.len() != 1 {
+        return None;
+    }
+    // we JUST checked that raw.len() == 1, so raw[0] WILL exist.
+    from_one_semicolon_delimited(&raw[0][..])
+}
+
+/// Reads a semicolon-delimited raw string into a Vec.
+pub fn from_one_semicolon_delimited<T: str::FromStr>(raw: &[u8]) -> Option<Vec<T>> {
+    from_one_raw_semicolon_delimited(raw).map(|parts| {
+        parts.into_iter().filter_map(|x| x.parse().ok()).collect()
+    })
+}
+
+/// Reads a semicolon-delimited raw string into a Vec of its raw element
+/// slices, without parsing each element via `FromStr`. See
+/// `from_one_raw_comma_delimited`.
+pub fn from_one_raw_semicolon_delimited(raw: &[u8]) -> Option<Vec<&str>> {
+    match str::from_utf8(raw) {
+        Ok(s) => Some(split_delimited(s, b';').into_iter().map(|x| x.trim()).collect()),
+        Err(_) => None
+    }
+}
+
+/// Parses a `token (";" token ["=" value])*` value, the shared shape
+/// behind `Content-Type`, `Content-Disposition`, and `Set-Cookie`'s
+/// attribute list. Returns the leading token together with an ordered list
+/// of `(name, value)` parameter pairs; a parameter with no `=value`
+/// (e.g. `no-cache`, as opposed to `max-age=0`) gets a `None` value.
+/// Parameter names are lowercased, since RFC 7231 token parameters are
+/// matched case-insensitively; a quoted `value` has its surrounding
+/// quotes and `\`-escapes removed.
+pub fn parse_params(raw: &str) -> Option<(&str, Vec<(String, Option<String>)>)> {
+    let mut parts = split_delimited(raw, b';').into_iter().map(|x| x.trim());
+    let value = match parts.next() {
+        Some(v) if !v.is_empty() => v,
+        _ => return None
+    };
+    let mut params = Vec::new();
+    for part in parts {
+        if part.is_empty() {
+            continue;
+        }
+        let (name, value) = match part.find('=') {
+            Some(i) => (&part[..i], Some(unquote(part[i + 1..].trim()))),
+            None => (part, None)
+        };
+        params.push((name.trim().to_lowercase(), value));
+    }
+    Some((value, params))
+}
+
+// Strips the surrounding quotes and `\`-escapes from a quoted-string
+// parameter value; an unquoted value is returned unchanged.
+fn unquote(s: &str) -> String {
+    if s.len() >= 2 && s.starts_with('"') && s.ends_with('"') {
+        let mut out = String::with_capacity(s.len() - 2);
+        let mut escaped = false;
+        for c in s[1..s.len() - 1].chars() {
+            if escaped {
+                out.push(c);
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    } else {
+        s.to_owned()
+    }
+}
+
+/// Splits a string on a delimiter byte, except where the delimiter falls
+/// inside a `"`-delimited quoted-string, so that elements like
+/// `Server-Timing`'s `desc="a, b"` survive intact instead of being cut
+/// in half. A `\` inside a quoted-string escapes the following byte, so
+/// `\"` does not end the quote.
+fn split_delimited(s: &str, delim: u8) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for (i, b) in s.bytes().enumerate() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match b {
+            b'\\' if in_quotes => escaped = true,
+            b'"' => in_quotes = !in_quotes,
+            b if b == delim && !in_quotes => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
 /// Format an array into a comma-delimited string.
 pub fn fmt_comma_delimited<T: fmt::Display>(fmt: &mut fmt::Formatter, parts: &[T]) -> fmt::Result {
     let last = parts.len() - 1;
@@ -52,73 +253,426 @@ pub fn fmt_comma_delimited<T: fmt::Display>(fmt: &mut fmt::Formatter, parts: &[T
     Ok(())
 }
 
-/// Get a Tm from HTTP date formats.
-//    Prior to 1995, there were three different formats commonly used by
-//   servers to communicate timestamps.  For compatibility with old
-//   implementations, all three are defined here.  The preferred format is
-//   a fixed-length and single-zone subset of the date and time
-//   specification used by the Internet Message Format [RFC5322].
-//
-//     HTTP-date    = IMF-fixdate / obs-date
-//
-//   An example of the preferred format is
-//
-//     Sun, 06 Nov 1994 08:49:37 GMT    ; IMF-fixdate
-//
-//   Examples of the two obsolete formats are
-//
-//     Sunday, 06-Nov-94 08:49:37 GMT   ; obsolete RFC 850 format
-//     Sun Nov  6 08:49:37 1994         ; ANSI C's asctime() format
-//
-//   A recipient that parses a timestamp value in an HTTP header field
-//   MUST accept all three HTTP-date formats.  When a sender generates a
-//   header field that contains one or more timestamps defined as
-//   HTTP-date, the sender MUST generate those timestamps in the
-//   IMF-fixdate format.
-pub fn tm_from_str(s: &str) -> Option<time::Tm> {
-    time::strptime(s, "%a, %d %b %Y %T %Z").or_else(|_| {
-        time::strptime(s, "%A, %d-%b-%y %T %Z")
-    }).or_else(|_| {
-        time::strptime(s, "%c")
-    }).ok()
+// Number of days from 0000-03-01 (the start of the proleptic Gregorian
+// calendar's "computational" year) to 1970-01-01, used to convert between
+// civil dates and a day count relative to the Unix epoch. See Howard
+// Hinnant's "chrono-Compatible Low-Level Date Algorithms" for a derivation
+// of `days_from_civil`/`days_to_civil`.
+const DAYS_TO_UNIX_EPOCH: i64 = 719468;
+
+fn floor_div(n: i64, d: i64) -> i64 {
+    if n >= 0 { n / d } else { (n - (d - 1)) / d }
+}
+
+/// Converts a (year, month, day) civil date into the number of days
+/// relative to 1970-01-01. `month` is 1-12, `day` is 1-31; both are
+/// assumed to already be validated.
+fn days_from_civil(y: i64, m: u32, d: u32) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = floor_div(y, 400);
+    let yoe = y - era * 400; // [0, 399]
+    let mp = (m as i64 + 9) % 12; // [0, 11], Mar=0 .. Feb=11
+    let doy = (153 * mp + 2) / 5 + d as i64 - 1; // [0, 365]
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy; // [0, 146096]
+    era * 146097 + doe - DAYS_TO_UNIX_EPOCH
+}
+
+/// The inverse of `days_from_civil`: turns a day count relative to
+/// 1970-01-01 back into a (year, month, day) civil date.
+fn days_to_civil(z: i64) -> (i64, u32, u32) {
+    let z = z + DAYS_TO_UNIX_EPOCH;
+    let era = floor_div(z, 146097);
+    let doe = z - era * 146097; // [0, 146096]
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365; // [0, 399]
+    let y = yoe + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100); // [0, 365]
+    let mp = (5 * doy + 2) / 153; // [0, 11]
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    (if m <= 2 { y + 1 } else { y }, m, d)
+}
+
+const MONTHS: [&'static [u8]; 12] = [
+    b"Jan", b"Feb", b"Mar", b"Apr", b"May", b"Jun",
+    b"Jul", b"Aug", b"Sep", b"Oct", b"Nov", b"Dec",
+];
+
+const WEEKDAYS_SHORT: [&'static [u8]; 7] = [
+    b"Sun", b"Mon", b"Tue", b"Wed", b"Thu", b"Fri", b"Sat",
+];
+
+const WEEKDAYS_LONG: [&'static [u8]; 7] = [
+    b"Sunday", b"Monday", b"Tuesday", b"Wednesday", b"Thursday", b"Friday", b"Saturday",
+];
+
+fn digit(b: u8) -> Option<u64> {
+    if b >= b'0' && b <= b'9' {
+        Some((b - b'0') as u64)
+    } else {
+        None
+    }
+}
+
+fn two_digits(b1: u8, b2: u8) -> Option<u64> {
+    match (digit(b1), digit(b2)) {
+        (Some(d1), Some(d2)) => Some(d1 * 10 + d2),
+        _ => None,
+    }
+}
+
+// Like `two_digits`, but allows the first digit to be a space, as in the
+// day field of asctime's `"Sun Nov  6 08:49:37 1994"`.
+fn two_digits_space_padded(b1: u8, b2: u8) -> Option<u64> {
+    if b1 == b' ' {
+        digit(b2)
+    } else {
+        two_digits(b1, b2)
+    }
+}
+
+fn four_digits(b1: u8, b2: u8, b3: u8, b4: u8) -> Option<u64> {
+    match (digit(b1), digit(b2), digit(b3), digit(b4)) {
+        (Some(d1), Some(d2), Some(d3), Some(d4)) => Some(d1 * 1000 + d2 * 100 + d3 * 10 + d4),
+        _ => None,
+    }
+}
+
+fn parse_month(b: &[u8]) -> Option<u32> {
+    MONTHS.iter().position(|m| *m == b).map(|i| i as u32 + 1)
+}
+
+fn is_weekday_short(b: &[u8]) -> bool {
+    WEEKDAYS_SHORT.iter().any(|w| *w == b)
+}
+
+fn is_weekday_long(b: &[u8]) -> bool {
+    WEEKDAYS_LONG.iter().any(|w| *w == b)
+}
+
+/// Why an `HttpDate` failed to parse.
+///
+/// This distinguishes a string that never matched any of the three
+/// RFC 7231 date shapes from one that did, but with a field outside its
+/// valid range (e.g. an hour of 24, or Feb 30) — the latter is a stronger signal
+/// that the sender intended a date and simply got a field wrong.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DateError {
+    /// The string didn't match the IMF-fixdate, RFC 850, or asctime shape.
+    InvalidFormat,
+    /// The string had the shape of an HTTP-date, but a field was out of range.
+    OutOfRange,
+}
+
+impl fmt::Display for DateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DateError::InvalidFormat => f.write_str("not a recognized HTTP-date format"),
+            DateError::OutOfRange => f.write_str("HTTP-date field out of range"),
+        }
+    }
+}
+
+impl error::Error for DateError {
+    fn description(&self) -> &str {
+        match *self {
+            DateError::InvalidFormat => "not a recognized HTTP-date format",
+            DateError::OutOfRange => "HTTP-date field out of range",
+        }
+    }
+}
+
+// Combines a validated civil date and time-of-day into seconds since the
+// Unix epoch, rejecting out-of-range fields and dates before 1970 (this
+// module only ever needs to represent HTTP-date values, which in practice
+// are never that old).
+fn to_timestamp(year: u64, month: u32, day: u32, hour: u64, min: u64, sec: u64) -> Result<u64, DateError> {
+    if day < 1 || day > days_in_month(year, month) || hour > 23 || min > 59 || sec > 59 || year > 9999 {
+        return Err(DateError::OutOfRange);
+    }
+    let days = days_from_civil(year as i64, month, day);
+    if days < 0 {
+        return Err(DateError::OutOfRange);
+    }
+    Ok(days as u64 * 86_400 + hour * 3600 + min * 60 + sec)
+}
+
+fn is_leap_year(year: u64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+// `month` is assumed to already be validated as 1-12 by `parse_month`.
+fn days_in_month(year: u64, month: u32) -> u32 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 => if is_leap_year(year) { 29 } else { 28 },
+        _ => 0,
+    }
+}
+
+// "Sun, 06 Nov 1994 08:49:37 GMT", the preferred IMF-fixdate format.
+fn parse_imf_fixdate(b: &[u8]) -> Result<u64, DateError> {
+    if !is_weekday_short(&b[0..3]) ||
+       b[3] != b',' || b[4] != b' ' || b[7] != b' ' || b[11] != b' ' ||
+       b[16] != b' ' || b[19] != b':' || b[22] != b':' || b[25] != b' ' ||
+       &b[26..29] != b"GMT" {
+        return Err(DateError::InvalidFormat);
+    }
+    let day = match two_digits(b[5], b[6]) { Some(d) => d as u32, None => return Err(DateError::InvalidFormat) };
+    let month = match parse_month(&b[8..11]) { Some(m) => m, None => return Err(DateError::InvalidFormat) };
+    let year = match four_digits(b[12], b[13], b[14], b[15]) { Some(y) => y, None => return Err(DateError::InvalidFormat) };
+    let hour = match two_digits(b[17], b[18]) { Some(h) => h, None => return Err(DateError::InvalidFormat) };
+    let min = match two_digits(b[20], b[21]) { Some(m) => m, None => return Err(DateError::InvalidFormat) };
+    let sec = match two_digits(b[23], b[24]) { Some(s) => s, None => return Err(DateError::InvalidFormat) };
+    to_timestamp(year, month, day, hour, min, sec)
+}
+
+// "Sun Nov  6 08:49:37 1994", ANSI C's asctime() format.
+fn parse_asctime(b: &[u8]) -> Result<u64, DateError> {
+    if !is_weekday_short(&b[0..3]) ||
+       b[3] != b' ' || b[7] != b' ' || b[10] != b' ' ||
+       b[13] != b':' || b[16] != b':' || b[19] != b' ' {
+        return Err(DateError::InvalidFormat);
+    }
+    let month = match parse_month(&b[4..7]) { Some(m) => m, None => return Err(DateError::InvalidFormat) };
+    let day = match two_digits_space_padded(b[8], b[9]) { Some(d) => d as u32, None => return Err(DateError::InvalidFormat) };
+    let hour = match two_digits(b[11], b[12]) { Some(h) => h, None => return Err(DateError::InvalidFormat) };
+    let min = match two_digits(b[14], b[15]) { Some(m) => m, None => return Err(DateError::InvalidFormat) };
+    let sec = match two_digits(b[17], b[18]) { Some(s) => s, None => return Err(DateError::InvalidFormat) };
+    let year = match four_digits(b[20], b[21], b[22], b[23]) { Some(y) => y, None => return Err(DateError::InvalidFormat) };
+    to_timestamp(year, month, day, hour, min, sec)
+}
+
+// "Sunday, 06-Nov-94 08:49:37 GMT", the obsolete RFC 850 format. Unlike
+// the other two, the weekday name isn't a fixed width, so everything
+// after it is addressed relative to the end of the string instead.
+fn parse_rfc850(b: &[u8]) -> Result<u64, DateError> {
+    if b.len() < 6 + 2 + 22 || b.len() > 9 + 2 + 22 {
+        return Err(DateError::InvalidFormat);
+    }
+    let wday_len = b.len() - (2 + 22);
+    if !is_weekday_long(&b[0..wday_len]) || b[wday_len] != b',' || b[wday_len + 1] != b' ' {
+        return Err(DateError::InvalidFormat);
+    }
+    let r = &b[wday_len + 2..];
+    if r[2] != b'-' || r[6] != b'-' || r[9] != b' ' ||
+       r[12] != b':' || r[15] != b':' || r[18] != b' ' || &r[19..22] != b"GMT" {
+        return Err(DateError::InvalidFormat);
+    }
+    let day = match two_digits(r[0], r[1]) { Some(d) => d as u32, None => return Err(DateError::InvalidFormat) };
+    let month = match parse_month(&r[3..6]) { Some(m) => m, None => return Err(DateError::InvalidFormat) };
+    let yy = match two_digits(r[7], r[8]) { Some(y) => y, None => return Err(DateError::InvalidFormat) };
+    let year = if yy < 70 { 2000 + yy } else { 1900 + yy };
+    let hour = match two_digits(r[10], r[11]) { Some(h) => h, None => return Err(DateError::InvalidFormat) };
+    let min = match two_digits(r[13], r[14]) { Some(m) => m, None => return Err(DateError::InvalidFormat) };
+    let sec = match two_digits(r[16], r[17]) { Some(s) => s, None => return Err(DateError::InvalidFormat) };
+    to_timestamp(year, month, day, hour, min, sec)
+}
+
+/// An HTTP-date value, as specified by RFC 7231, Section 7.1.1.1.
+///
+/// Stores the timestamp as seconds since the Unix epoch, so that parsing
+/// and formatting headers like `Date`, `Expires`, and `Last-Modified`
+/// never need to allocate or pull in a general-purpose date/time library.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct HttpDate(u64);
+
+impl HttpDate {
+    /// The number of seconds since the Unix epoch (1970-01-01T00:00:00Z).
+    pub fn as_secs(&self) -> u64 {
+        self.0
+    }
+}
+
+impl str::FromStr for HttpDate {
+    type Err = DateError;
+
+    // A recipient MUST accept all three HTTP-date formats, though senders
+    // MUST only generate IMF-fixdate. Each format has a fixed byte length
+    // except RFC 850's weekday name, so dispatch on that first.
+    fn from_str(s: &str) -> Result<HttpDate, DateError> {
+        let b = s.as_bytes();
+        let secs = match b.len() {
+            29 => parse_imf_fixdate(b),
+            24 => parse_asctime(b),
+            _ => parse_rfc850(b),
+        };
+        secs.map(HttpDate)
+    }
+}
+
+impl fmt::Display for HttpDate {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt_http_date(fmt, self.0)
+    }
+}
+
+/// Formats a Unix timestamp as an IMF-fixdate, e.g.
+/// `Sun, 06 Nov 1994 08:49:37 GMT`. This is the only HTTP-date format a
+/// sender is allowed to generate.
+pub fn fmt_http_date(fmt: &mut fmt::Formatter, secs: u64) -> fmt::Result {
+    let days = (secs / 86_400) as i64;
+    let time_of_day = secs % 86_400;
+    let (year, month, day) = days_to_civil(days);
+    let weekday = WEEKDAYS_SHORT[((days + 4) % 7) as usize];
+    write!(fmt, "{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT",
+           str::from_utf8(weekday).unwrap(),
+           day,
+           str::from_utf8(MONTHS[(month - 1) as usize]).unwrap(),
+           year,
+           time_of_day / 3600,
+           (time_of_day % 3600) / 60,
+           time_of_day % 60)
 }
 
 #[cfg(test)]
 mod tests {
-    use time::Tm;
-    use super::tm_from_str;
-
-    const NOV_07: Tm = Tm {
-        tm_nsec: 0,
-        tm_sec: 37,
-        tm_min: 48,
-        tm_hour: 8,
-        tm_mday: 7,
-        tm_mon: 10,
-        tm_year: 94,
-        tm_wday: 0,
-        tm_isdst: 0,
-        tm_yday: 0,
-        tm_utcoff: 0,
-    };
+    use std::str::FromStr;
+    use super::HttpDate;
+
+    // Mon, 07 Nov 1994 08:48:37 GMT
+    const NOV_07: u64 = 784198117;
 
     #[test]
     fn test_imf_fixdate() {
-        assert_eq!(tm_from_str("Sun, 07 Nov 1994 08:48:37 GMT"),
-                   Some(NOV_07));
+        assert_eq!(HttpDate::from_str("Sun, 07 Nov 1994 08:48:37 GMT"),
+                   Ok(HttpDate(NOV_07)));
     }
 
     #[test]
     fn test_rfc_850() {
-        assert_eq!(tm_from_str("Sunday, 07-Nov-94 08:48:37 GMT"),
-                   Some(NOV_07));
+        assert_eq!(HttpDate::from_str("Sunday, 07-Nov-94 08:48:37 GMT"),
+                   Ok(HttpDate(NOV_07)));
     }
 
     #[test]
     fn test_asctime() {
-        assert_eq!(tm_from_str("Sun Nov  7 08:48:37 1994"),
-                   Some(NOV_07));
+        assert_eq!(HttpDate::from_str("Sun Nov  7 08:48:37 1994"),
+                   Ok(HttpDate(NOV_07)));
+    }
+
+    #[test]
+    fn test_fmt_http_date_always_emits_imf_fixdate() {
+        // The weekday in a parsed date is validated but not otherwise
+        // used, so formatting recomputes it from the timestamp: Nov 7
+        // 1994 was in fact a Monday, not the "Sun" used above.
+        let date = HttpDate::from_str("Sun, 07 Nov 1994 08:48:37 GMT").unwrap();
+        assert_eq!(format!("{}", date), "Mon, 07 Nov 1994 08:48:37 GMT");
+    }
+
+    #[test]
+    fn test_rejects_invalid_month() {
+        assert_eq!(HttpDate::from_str("Sun, 07 Foo 1994 08:48:37 GMT"),
+                   Err(super::DateError::InvalidFormat));
+    }
+
+    #[test]
+    fn test_rejects_out_of_range_hour() {
+        assert_eq!(HttpDate::from_str("Sun, 07 Nov 1994 24:48:37 GMT"),
+                   Err(super::DateError::OutOfRange));
     }
 
+    #[test]
+    fn test_rejects_day_out_of_range_for_month() {
+        // 2025 is not a leap year, so February only has 28 days.
+        assert_eq!(HttpDate::from_str("Sun, 29 Feb 2025 00:00:00 GMT"),
+                   Err(super::DateError::OutOfRange));
+        assert_eq!(HttpDate::from_str("Mon, 31 Feb 2025 00:00:00 GMT"),
+                   Err(super::DateError::OutOfRange));
+    }
+
+    #[test]
+    fn test_accepts_leap_day() {
+        assert!(HttpDate::from_str("Sun, 29 Feb 2004 00:00:00 GMT").is_ok());
+    }
+
+    #[test]
+    fn test_from_one_raw_comma_delimited_splits_plain_list() {
+        assert_eq!(super::from_one_raw_comma_delimited(b"a, b, c"),
+                   Some(vec!["a", "b", "c"]));
+    }
+
+    #[test]
+    fn test_from_one_raw_comma_delimited_ignores_commas_in_quotes() {
+        assert_eq!(super::from_one_raw_comma_delimited(br#"Basic realm="a, b", error="c""#),
+                   Some(vec![r#"Basic realm="a, b""#, r#"error="c""#]));
+    }
+
+    #[test]
+    fn test_from_one_raw_comma_delimited_honors_escaped_quotes() {
+        assert_eq!(super::from_one_raw_comma_delimited(br#"a="x\", y", b=z"#),
+                   Some(vec![r#"a="x\", y""#, "b=z"]));
+    }
+
+    #[test]
+    fn test_from_one_comma_delimited_parses_elements() {
+        assert_eq!(super::from_one_comma_delimited::<u32>(b"1, 2, 3"),
+                   Some(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn test_from_one_comma_delimited_skips_unparseable_elements() {
+        // Keeps the legacy tolerant behavior: one bad element doesn't
+        // nuke the whole header.
+        assert_eq!(super::from_one_comma_delimited::<u32>(b"1, two, 3"),
+                   Some(vec![1, 3]));
+    }
+
+    #[test]
+    fn test_try_from_one_comma_delimited_fails_whole_header_on_bad_element() {
+        assert_eq!(super::try_from_one_comma_delimited::<u32>(b"1, two, 3"),
+                   Err(super::HeaderError::Parse));
+    }
 
+    #[test]
+    fn test_from_one_raw_semicolon_delimited_ignores_semicolons_in_quotes() {
+        assert_eq!(super::from_one_raw_semicolon_delimited(br#"a="x;y"; b=z"#),
+                   Some(vec![r#"a="x;y""#, "b=z"]));
+    }
+
+    #[test]
+    fn test_parse_params_content_type() {
+        assert_eq!(super::parse_params("text/html; charset=utf-8"),
+                   Some(("text/html", vec![("charset".to_owned(), Some("utf-8".to_owned()))])));
+    }
+
+    #[test]
+    fn test_parse_params_optional_value_directive() {
+        assert_eq!(super::parse_params("sessionid=38af; HttpOnly; Max-Age=3600"),
+                   Some(("sessionid=38af", vec![
+                       ("httponly".to_owned(), None),
+                       ("max-age".to_owned(), Some("3600".to_owned())),
+                   ])));
+    }
+
+    #[test]
+    fn test_parse_params_unquotes_and_lowercases_names() {
+        assert_eq!(super::parse_params(r#"attachment; FileName="a\"b.txt""#),
+                   Some(("attachment", vec![("filename".to_owned(), Some(r#"a"b.txt"#.to_owned()))])));
+    }
+
+    #[test]
+    fn test_try_from_one_raw_str_reports_not_one_line() {
+        let raw: &[Vec<u8>] = &[b"1".to_vec(), b"2".to_vec()];
+        assert_eq!(super::try_from_one_raw_str::<u32>(raw), Err(super::HeaderError::NotOneLine));
+    }
+
+    #[test]
+    fn test_try_from_one_raw_str_reports_parse_failure() {
+        let raw: &[Vec<u8>] = &[b"not a number".to_vec()];
+        assert_eq!(super::try_from_one_raw_str::<u32>(raw), Err(super::HeaderError::Parse));
+    }
+
+    #[test]
+    fn test_try_from_comma_delimited_reports_element_parse_failure() {
+        let raw: &[Vec<u8>] = &[b"1, two, 3".to_vec()];
+        assert_eq!(super::try_from_comma_delimited::<u32>(raw), Err(super::HeaderError::Parse));
+    }
+
+    #[test]
+    fn test_try_http_date_from_one_raw_str_reports_out_of_range() {
+        let raw: &[Vec<u8>] = &[b"Sun, 07 Nov 1994 24:48:37 GMT".to_vec()];
+        assert_eq!(super::try_http_date_from_one_raw_str(raw),
+                   Err(super::HeaderError::Date(super::DateError::OutOfRange)));
+    }
 }